@@ -1,12 +1,19 @@
+use std::fs;
+
 use clap::{self, App, Arg, ArgMatches};
 use clap::{crate_authors, crate_version, crate_name, crate_description};
 
+use crate::strategy::MmapPreference;
+use crate::walk::WalkOptions;
+
 
 #[derive(Debug)]
 pub enum Output {
   FileName,
   Bytes,
-  Offset
+  Offset,
+  Json,
+  Count
 }
 
 impl Default for Output {
@@ -18,14 +25,19 @@ impl Default for Output {
 pub struct Options {
   pub inverse: bool,
   pub case_insensitive: bool,
-  pub output: Output
+  pub hex: bool,
+  pub output: Output,
+  pub context_before: usize,
+  pub context_after: usize,
+  pub mmap: MmapPreference,
+  pub walk: WalkOptions
 }
 
 
 #[derive(Default, Debug)]
 pub struct Args {
   pub options: Options,
-  pub pattern: String,
+  pub pattern: Box<[String]>,
   pub files: Box<[String]>
 }
 
@@ -53,7 +65,7 @@ fn build_app() -> App<'static, 'static> {
     // Positional arguments:
     .arg(
       Arg::with_name("pattern")
-          .required(true)
+          .required_unless_one(&["regexp", "file"])
           .index(1)
     )
     .arg(
@@ -61,6 +73,56 @@ fn build_app() -> App<'static, 'static> {
         .multiple(true)
         .index(2)
     )
+    // Pattern flags:
+    .arg(
+      Arg::with_name("regexp")
+        .short("e")
+        .long("regexp")
+        .value_name("PATTERN")
+        .takes_value(true)
+        .number_of_values(1)
+        .multiple(true)
+        .allow_hyphen_values(true)
+        .help("specify a pattern, may be given more than once")
+    )
+    .arg(
+      Arg::with_name("file")
+        .short("f")
+        .long("file")
+        .value_name("FILE")
+        .takes_value(true)
+        .number_of_values(1)
+        .multiple(true)
+        .allow_hyphen_values(true)
+        .help("read patterns from FILE, one per line")
+    )
+    // Walk flags:
+    .arg(
+      Arg::with_name("recursive")
+        .short("r")
+        .long("recursive")
+        .help("recurse into directories given in <files>")
+    )
+    .arg(
+      Arg::with_name("glob")
+        .short("g")
+        .long("glob")
+        .value_name("GLOB")
+        .takes_value(true)
+        .number_of_values(1)
+        .multiple(true)
+        .help("include/exclude (!GLOB) files matching GLOB, may be given more than once")
+    )
+    .arg(
+      Arg::with_name("type")
+        .short("t")
+        .long("type")
+        .value_name("NAME")
+        .takes_value(true)
+        .number_of_values(1)
+        .multiple(true)
+        .help("only search files of type NAME")
+    )
     // Matching flags:
     .arg(
       Arg::with_name("invert-match")
@@ -74,6 +136,50 @@ fn build_app() -> App<'static, 'static> {
         .long("ignore-case")
         .help("case insensitive matching")
     )
+    .arg(
+      Arg::with_name("hex")
+        .short("x")
+        .long("hex")
+        .help("interpret <pattern> as a hex DSL (`DE AD ?? A? [00-1F]`) instead of a literal/regex")
+    )
+    // Context flags:
+    .arg(
+      Arg::with_name("after")
+        .short("A")
+        .long("after")
+        .value_name("N")
+        .takes_value(true)
+        .help("print N bytes of context after each match")
+    )
+    .arg(
+      Arg::with_name("before")
+        .short("B")
+        .long("before")
+        .value_name("N")
+        .takes_value(true)
+        .help("print N bytes of context before each match")
+    )
+    .arg(
+      Arg::with_name("context")
+        .short("C")
+        .long("context")
+        .value_name("N")
+        .takes_value(true)
+        .help("print N bytes of context before and after each match (shorthand for -A N -B N)")
+    )
+    // Strategy flags:
+    .arg(
+      Arg::with_name("mmap")
+        .long("mmap")
+        .help("always memory-map file inputs (default: auto, based on file size)")
+        .overrides_with("no-mmap")
+    )
+    .arg(
+      Arg::with_name("no-mmap")
+        .long("no-mmap")
+        .help("never memory-map file inputs; always stream with a buffered reader")
+        .overrides_with("mmap")
+    )
     // Output flags:
     .arg(
       Arg::with_name("only-matching")
@@ -84,6 +190,8 @@ fn build_app() -> App<'static, 'static> {
           "byte-offset",
           "files-with-matches",
           "files-without-matches",
+          "json",
+          "count",
         ])
     )
     .arg(
@@ -95,6 +203,8 @@ fn build_app() -> App<'static, 'static> {
           "only-matching",
           "files-with-matches",
           "files-without-matches",
+          "json",
+          "count",
         ])
     )
     .arg(
@@ -106,6 +216,8 @@ fn build_app() -> App<'static, 'static> {
           "only-matching",
           "byte-offset",
           "files-without-matches",
+          "json",
+          "count",
         ])
     )
     .arg(
@@ -117,20 +229,86 @@ fn build_app() -> App<'static, 'static> {
           "only-matching",
           "byte-offset",
           "files-with-matches",
+          "json",
+          "count",
+        ])
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .help("print matches as JSON Lines, with a per-file summary object")
+        .overrides_with_all(&[
+          "only-matching",
+          "byte-offset",
+          "files-with-matches",
+          "files-without-matches",
+          "count",
+        ])
+    )
+    .arg(
+      Arg::with_name("count")
+        .short("c")
+        .long("count")
+        .help("print only a count of matches (or non-matches, with -v) per file")
+        .overrides_with_all(&[
+          "only-matching",
+          "byte-offset",
+          "files-with-matches",
+          "files-without-matches",
+          "json",
         ])
     )
 }
 
 
-fn build_args<'a>(args: ArgMatches<'a>) -> Args {
-  let pattern = String::from(
-    args.value_of("pattern")
-        .expect("<pattern> not in ArgMatches") // pattern is required.
-  );
+fn patterns_from_file(path: &str) -> Result<Vec<String>, Error> {
+  let contents = fs::read_to_string(path)
+    .map_err(|e| Error { message: format!("{}: {}", path, e) })?;
+
+  Ok(
+    contents.lines()
+      .filter(|line| !line.is_empty())
+      .map(String::from)
+      .collect()
+  )
+}
+
+fn build_args<'a>(args: ArgMatches<'a>) -> Result<Args, Error> {
+  // Once -e/-f are given, the grammar matches ripgrep's: the positional
+  // that would otherwise be <pattern> is just the first path to search.
+  let explicit_patterns = args.is_present("regexp") || args.is_present("file");
 
-  let files = match args.values_of("files") {
-    None     => Box::new([String::from("-")]) as Box<[String]>, // Input from stdin.
-    Some(fs) => fs.map(String::from).collect()
+  let mut pattern = Vec::new();
+  let mut files: Vec<String> = Vec::new();
+
+  match args.value_of("pattern") {
+    Some(p) if explicit_patterns => files.push(String::from(p)),
+    Some(p)                      => pattern.push(String::from(p)),
+    None                         => {}
+  }
+
+  if let Some(es) = args.values_of("regexp") {
+    pattern.extend(es.map(String::from));
+  }
+
+  if let Some(paths) = args.values_of("file") {
+    for path in paths {
+      pattern.extend(patterns_from_file(path)?);
+    }
+  }
+
+  if pattern.is_empty() {
+    return Err(Error { message: String::from("no pattern given; use <pattern>, -e, or -f") });
+  }
+
+  if let Some(fs) = args.values_of("files") {
+    files.extend(fs.map(String::from));
+  }
+
+  let files = if files.is_empty() {
+    Box::new([String::from("-")]) as Box<[String]> // Input from stdin.
+  } else {
+    files.into_boxed_slice()
   };
 
   let flag = |f| args.is_present(f);
@@ -139,26 +317,55 @@ fn build_args<'a>(args: ArgMatches<'a>) -> Args {
     flag("only-matching"),
     flag("byte-offset"),
     flag("files-with-matches"),
-    flag("files-without-matches")
+    flag("files-without-matches"),
+    flag("json"),
+    flag("count")
   );
 
   let output = match output_flags {
-    (true, _, _, _) => Output::Bytes,
-    (_, true, _, _) => Output::Offset,
-    (_, _, true, _) => Output::FileName,
-    (_, _, _, true) => Output::FileName,
-    (_, _, _, _)    => Default::default(),
+    (true, _, _, _, _, _) => Output::Bytes,
+    (_, true, _, _, _, _) => Output::Offset,
+    (_, _, true, _, _, _) => Output::FileName,
+    (_, _, _, true, _, _) => Output::FileName,
+    (_, _, _, _, true, _) => Output::Json,
+    (_, _, _, _, _, true) => Output::Count,
+    (_, _, _, _, _, _)    => Default::default(),
+  };
+
+  let context_value = |name: &str| -> Result<Option<usize>, Error> {
+    args.value_of(name)
+      .map(|v| v.parse::<usize>().map_err(|_| Error { message: format!("invalid value for --{}: {:?}", name, v) }))
+      .transpose()
   };
 
-  Args {
+  let context = context_value("context")?;
+  let context_before = context_value("before")?.or(context).unwrap_or(0);
+  let context_after = context_value("after")?.or(context).unwrap_or(0);
+
+  let globs: Vec<String> = args.values_of("glob")
+    .map_or_else(Vec::new, |gs| gs.map(String::from).collect());
+
+  let types: Vec<String> = args.values_of("type")
+    .map_or_else(Vec::new, |ts| ts.map(String::from).collect());
+
+  Ok(Args {
     options: Options {
       inverse: flag("invert-match") ^ flag("files-without-matches"), // (-L) is (-vl).
       case_insensitive: args.is_present("ignore-case"),
-      output
+      hex: flag("hex"),
+      output,
+      context_before,
+      context_after,
+      mmap: match (flag("mmap"), flag("no-mmap")) {
+        (true, _) => MmapPreference::Always,
+        (_, true) => MmapPreference::Never,
+        (_, _)    => MmapPreference::Auto
+      },
+      walk: WalkOptions::build(flag("recursive"), &globs, &types)?
     },
-    pattern,
+    pattern: pattern.into_boxed_slice(),
     files
-  }
+  })
 }
 
 
@@ -166,7 +373,7 @@ pub fn parse() -> Result<Command, Error> {
   let app = build_app();
 
   match app.get_matches_safe() {
-    Ok(arg_matches) => Ok(Command::Grep(build_args(arg_matches))),
+    Ok(arg_matches) => build_args(arg_matches).map(Command::Grep),
     Err(e) => match e.kind {
       clap::ErrorKind::HelpDisplayed    => Ok(Command::Help(e.message)),
       clap::ErrorKind::VersionDisplayed => Ok(Command::Version(e.message)),
@@ -0,0 +1,29 @@
+/// A byte range `[start, end)` of a file to display as context.
+pub type Window = (usize, usize);
+
+/// The context window around a single match at `[offset, offset + len)`,
+/// clamped to `[0, file_len)`.
+pub fn window(offset: usize, len: usize, before: usize, after: usize, file_len: usize) -> Window {
+  let start = offset.saturating_sub(before);
+  let end = (offset + len + after).min(file_len);
+  (start, end)
+}
+
+/// Merges overlapping or adjacent windows into the smallest set of
+/// contiguous blocks, in order. Mirrors grep's behaviour of folding nearby
+/// `-A`/`-B`/`-C` context into one block instead of printing a `--`
+/// separator between windows that already touch.
+pub fn merge_windows(mut windows: Vec<Window>) -> Vec<Window> {
+  windows.sort_unstable_by_key(|&(start, _)| start);
+
+  let mut merged: Vec<Window> = Vec::with_capacity(windows.len());
+
+  for (start, end) in windows {
+    match merged.last_mut() {
+      Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+      _ => merged.push((start, end))
+    }
+  }
+
+  merged
+}
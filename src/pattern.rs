@@ -0,0 +1,78 @@
+use crate::args::Error;
+
+
+/// A single compiled position in a `--hex` pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+  /// Matches when `(byte & mask) == value`. Full wildcards (`?`/`??`) are
+  /// `mask = 0x00`; a literal byte is `mask = 0xff`; a single-nibble
+  /// wildcard (`A?`/`?F`) masks out the other nibble.
+  Masked { value: u8, mask: u8 },
+  /// Matches any byte in `low..=high`, from a `[XX-YY]` token.
+  Range { low: u8, high: u8 }
+}
+
+impl Token {
+  pub fn matches(&self, byte: u8) -> bool {
+    match *self {
+      Token::Masked { value, mask } => (byte & mask) == value,
+      Token::Range { low, high }    => low <= byte && byte <= high
+    }
+  }
+}
+
+
+/// Compiles a `--hex` DSL pattern (space-separated tokens, e.g. `"DE AD ?? A?"`)
+/// into a sequence of `Token`s to match byte-by-byte against input.
+pub fn compile(pattern: &str) -> Result<Vec<Token>, Error> {
+  pattern.split_whitespace()
+    .map(compile_token)
+    .collect()
+}
+
+fn compile_token(token: &str) -> Result<Token, Error> {
+  if let Some(range) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+    return compile_range(range);
+  }
+
+  match token {
+    "?" | "??" => Ok(Token::Masked { value: 0x00, mask: 0x00 }),
+    _ if token.chars().count() == 2 => compile_byte(token),
+    _ => Err(invalid_token(token))
+  }
+}
+
+fn compile_byte(token: &str) -> Result<Token, Error> {
+  let mut chars = token.chars();
+  let hi = chars.next().unwrap();
+  let lo = chars.next().unwrap();
+
+  match (nibble(hi), nibble(lo)) {
+    (Some(hi), Some(lo)) => Ok(Token::Masked { value: (hi << 4) | lo, mask: 0xff }),
+    (Some(hi), None) if lo == '?' => Ok(Token::Masked { value: hi << 4, mask: 0xf0 }),
+    (None, Some(lo)) if hi == '?' => Ok(Token::Masked { value: lo, mask: 0x0f }),
+    _ => Err(invalid_token(token))
+  }
+}
+
+fn compile_range(range: &str) -> Result<Token, Error> {
+  let (low, high) = range.split_once('-')
+    .ok_or_else(|| invalid_token(range))?;
+
+  let low = u8::from_str_radix(low, 16).map_err(|_| invalid_token(low))?;
+  let high = u8::from_str_radix(high, 16).map_err(|_| invalid_token(high))?;
+
+  if low > high {
+    return Err(Error { message: format!("invalid range [{:X}-{:X}]: low > high", low, high) });
+  }
+
+  Ok(Token::Range { low, high })
+}
+
+fn nibble(c: char) -> Option<u8> {
+  c.to_digit(16).map(|d| d as u8)
+}
+
+fn invalid_token(token: &str) -> Error {
+  Error { message: format!("invalid --hex token: {:?}", token) }
+}
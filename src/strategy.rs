@@ -0,0 +1,42 @@
+/// Files at or above this size are memory-mapped when mmap is permitted.
+pub const MMAP_THRESHOLD: u64 = 1 << 20; // 1 MiB
+
+
+/// How a given input should be searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+  /// Memory-map the file and search the mapped slice directly.
+  Mmap,
+  /// Read through a fixed-size buffer, carrying a `pattern_len - 1` byte
+  /// overlap between chunks so matches spanning a chunk boundary aren't missed.
+  Stream
+}
+
+
+/// The user's `--mmap`/`--no-mmap` preference. `Auto` (the default) lets
+/// bgrep pick a strategy per file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MmapPreference {
+  Auto,
+  Always,
+  Never
+}
+
+impl Default for MmapPreference {
+  fn default() -> MmapPreference { MmapPreference::Auto }
+}
+
+impl MmapPreference {
+  /// Chooses a search strategy for an input of `len` bytes, or `None` for
+  /// stdin and other non-seekable inputs, which always stream.
+  pub fn strategy(&self, len: Option<u64>) -> Strategy {
+    match (self, len) {
+      (MmapPreference::Never, _)        => Strategy::Stream,
+      (_, None)                         => Strategy::Stream,
+      (MmapPreference::Always, Some(_)) => Strategy::Mmap,
+      (MmapPreference::Auto, Some(len)) => {
+        if len >= MMAP_THRESHOLD { Strategy::Mmap } else { Strategy::Stream }
+      }
+    }
+  }
+}
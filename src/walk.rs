@@ -0,0 +1,261 @@
+use fnv::FnvHashMap;
+use regex::RegexSet;
+
+use crate::args::Error;
+
+
+/// Built-in `--type` definitions, expanded to the globs they stand for.
+const TYPES: &[(&str, &[&str])] = &[
+  ("rust", &["*.rs"]),
+  ("c", &["*.c", "*.h"]),
+  ("cpp", &["*.cc", "*.cpp", "*.cxx", "*.hh", "*.hpp"]),
+  ("py", &["*.py"]),
+  ("js", &["*.js", "*.jsx"]),
+  ("go", &["*.go"]),
+  ("elf", &["*.elf", "*.so", "*.o"]),
+];
+
+fn type_globs(name: &str) -> Option<&'static [&'static str]> {
+  TYPES.iter()
+    .find(|(type_name, _)| *type_name == name)
+    .map(|(_, globs)| *globs)
+}
+
+
+#[derive(Debug, Clone, Copy)]
+struct Glob {
+  index: usize,
+  negated: bool
+}
+
+
+/// Compiles `--glob`/`--type` patterns into a matcher that can be probed
+/// once per candidate path without re-parsing or re-compiling anything.
+///
+/// Patterns are bucketed at build time so that the common cases (an exact
+/// name, or a bare `*.ext`) never touch the regex engine:
+///   1. no wildcard characters  -> exact path/basename lookup
+///   2. a single `*.ext`        -> bare extension lookup
+///   3. anything else           -> compiled into a `RegexSet`
+///
+/// Later globs override earlier ones, matching ripgrep's `--glob` semantics:
+/// the highest-indexed glob that matches a candidate decides whether it is
+/// included (or excluded, if the glob was `!`-prefixed).
+#[derive(Debug)]
+pub struct GlobMatcher {
+  exact: FnvHashMap<String, Vec<Glob>>,
+  extensions: FnvHashMap<String, Vec<Glob>>,
+  regexes: RegexSet,
+  regex_globs: Vec<Glob>
+}
+
+impl GlobMatcher {
+  pub fn build(patterns: &[String]) -> Result<GlobMatcher, Error> {
+    let mut exact: FnvHashMap<String, Vec<Glob>> = FnvHashMap::default();
+    let mut extensions: FnvHashMap<String, Vec<Glob>> = FnvHashMap::default();
+    let mut regex_patterns = Vec::new();
+    let mut regex_globs = Vec::new();
+
+    for (index, raw) in patterns.iter().enumerate() {
+      let (negated, glob) = match raw.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None       => (false, raw.as_str())
+      };
+
+      let entry = Glob { index, negated };
+
+      if let Some(ext) = bare_extension(glob) {
+        extensions.entry(String::from(ext)).or_insert_with(Vec::new).push(entry);
+      } else if !has_wildcard(glob) {
+        exact.entry(String::from(glob)).or_insert_with(Vec::new).push(entry);
+      } else {
+        regex_patterns.push(glob_to_regex(glob)?);
+        regex_globs.push(entry);
+      }
+    }
+
+    let regexes = RegexSet::new(&regex_patterns)
+      .map_err(|e| Error { message: format!("invalid --glob pattern: {}", e) })?;
+
+    Ok(GlobMatcher {
+      exact,
+      extensions,
+      regexes,
+      regex_globs
+    })
+  }
+
+  /// Whether `path` should be kept, applying ripgrep's "last matching glob
+  /// wins" override rule. With no globs at all, everything matches; a path
+  /// that no glob matches is kept only when all globs are `!` exclusions.
+  pub fn is_match(&self, path: &str) -> bool {
+    if self.exact.is_empty() && self.extensions.is_empty() && self.regex_globs.is_empty() {
+      return true;
+    }
+
+    let basename = path.rsplit('/').next().unwrap_or(path);
+    let mut winner: Option<Glob> = None;
+
+    let mut consider = |candidates: &[Glob]| {
+      for &glob in candidates {
+        if winner.map_or(true, |w| glob.index > w.index) {
+          winner = Some(glob);
+        }
+      }
+    };
+
+    if let Some(candidates) = self.exact.get(path) {
+      consider(candidates);
+    }
+    if let Some(candidates) = self.exact.get(basename) {
+      consider(candidates);
+    }
+    if let Some(ext) = extension_of(basename) {
+      if let Some(candidates) = self.extensions.get(ext) {
+        consider(candidates);
+      }
+    }
+    for i in self.regexes.matches(path).into_iter() {
+      consider(&self.regex_globs[i..=i]);
+    }
+
+    match winner {
+      Some(glob) => !glob.negated,
+      // A path no glob matched is kept only when every glob is a `!` exclusion.
+      None => self.exact.values()
+        .chain(self.extensions.values())
+        .flatten()
+        .chain(self.regex_globs.iter())
+        .all(|glob| glob.negated)
+    }
+  }
+}
+
+impl Default for GlobMatcher {
+  fn default() -> GlobMatcher { GlobMatcher::build(&[]).expect("no patterns never fails to compile") }
+}
+
+
+fn has_wildcard(glob: &str) -> bool {
+  glob.chars().any(|c| matches!(c, '*' | '?' | '[' | ']' | '{' | '}'))
+}
+
+/// A single-component `*.ext` glob, e.g. `*.rs`. Multi-dot extensions like
+/// `*.tar.gz` are NOT bare extensions (`extension_of` would only ever see
+/// `"gz"`) and fall through to the regex bucket instead.
+fn bare_extension(glob: &str) -> Option<&str> {
+  glob.strip_prefix("*.").filter(|rest| !rest.contains('.') && !has_wildcard(rest))
+}
+
+fn extension_of(basename: &str) -> Option<&str> {
+  basename.rsplit_once('.').map(|(_, ext)| ext)
+}
+
+/// Translates a glob to a regex with directory-boundary-aware wildcards:
+/// `*` and `?` never cross a `/`, while `**` does (and so can span
+/// directories), matching familiar shell/ripgrep glob semantics. A glob
+/// with no `/` of its own is anchored so it can match a file at any depth,
+/// the same way a bare `*.ext` glob does in the extension bucket.
+fn glob_to_regex(glob: &str) -> Result<String, Error> {
+  let mut pattern = String::from("^");
+
+  if !glob.contains('/') {
+    pattern.push_str("(?:.*/)?");
+  }
+
+  let mut chars = glob.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        pattern.push_str(".*");
+      },
+      '*' => pattern.push_str("[^/]*"),
+      '?' => pattern.push_str("[^/]"),
+      '[' => {
+        pattern.push('[');
+        if chars.peek() == Some(&'!') {
+          chars.next();
+          pattern.push('^');
+        }
+        loop {
+          match chars.next() {
+            Some(']') => { pattern.push(']'); break; },
+            Some(c)   => pattern.push(c),
+            None      => return Err(unterminated_glob(glob, '['))
+          }
+        }
+      },
+      '{' => {
+        let mut branch = String::new();
+        let mut branches = Vec::new();
+
+        loop {
+          match chars.next() {
+            Some(',') => { branches.push(escape_literal(&branch)); branch.clear(); },
+            Some('}') => { branches.push(escape_literal(&branch)); break; },
+            Some(c)   => branch.push(c),
+            None      => return Err(unterminated_glob(glob, '{'))
+          }
+        }
+
+        pattern.push_str("(?:");
+        pattern.push_str(&branches.join("|"));
+        pattern.push(')');
+      },
+      '.' | '+' | '(' | ')' | '|' | '^' | '$' | '\\' => {
+        pattern.push('\\');
+        pattern.push(c);
+      },
+      other => pattern.push(other)
+    }
+  }
+
+  pattern.push('$');
+  Ok(pattern)
+}
+
+fn escape_literal(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+
+  for c in s.chars() {
+    if matches!(c, '.' | '+' | '(' | ')' | '|' | '^' | '$' | '\\' | '*' | '?' | '[' | ']') {
+      out.push('\\');
+    }
+    out.push(c);
+  }
+
+  out
+}
+
+fn unterminated_glob(glob: &str, opener: char) -> Error {
+  Error { message: format!("unterminated '{}' in glob {:?}", opener, glob) }
+}
+
+
+/// How `files` should be expanded before searching.
+#[derive(Default, Debug)]
+pub struct WalkOptions {
+  pub recursive: bool,
+  pub matcher: GlobMatcher
+}
+
+impl WalkOptions {
+  pub fn build(recursive: bool, globs: &[String], types: &[String]) -> Result<WalkOptions, Error> {
+    let mut patterns: Vec<String> = Vec::new();
+
+    for name in types {
+      if let Some(globs) = type_globs(name) {
+        patterns.extend(globs.iter().map(|g| String::from(*g)));
+      }
+    }
+
+    patterns.extend(globs.iter().cloned());
+
+    Ok(WalkOptions {
+      recursive,
+      matcher: GlobMatcher::build(&patterns)?
+    })
+  }
+}